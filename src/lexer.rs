@@ -1,15 +1,25 @@
+use std::borrow::Cow;
+
 #[derive(Debug, PartialEq)]
 enum TokenType {
-    Illegal,
     EOF,
 
     // Identifiers + Literals
     Ident,
     Int,
+    String,
 
     // Operators
     Assign,
     Plus,
+    Minus,
+    Bang,
+    Asterisk,
+    Slash,
+    Lt,
+    Gt,
+    Eq,
+    NotEq,
 
     // Delimiters
     Comma,
@@ -23,17 +33,47 @@ enum TokenType {
     // Keywords
     Function,
     Let,
+    True,
+    False,
+    If,
+    Else,
+    Return,
+}
+
+#[derive(Debug, PartialEq)]
+struct Token<'src>(TokenType, Cow<'src, str>);
+
+/// A line/column location within the source, both 1-indexed.
+#[derive(Debug, PartialEq, Clone, Copy)]
+struct Position {
+    line: usize,
+    column: usize,
 }
 
+/// A `Token` together with the span of source it was scanned from.
 #[derive(Debug, PartialEq)]
-struct Token(TokenType, Vec<char>);
+struct SpannedToken<'src> {
+    token: Token<'src>,
+    start: Position,
+    end: Position,
+}
+
+/// A recoverable lexing failure, with the position it occurred at.
+#[derive(Debug, PartialEq)]
+enum LexError {
+    UnexpectedChar { ch: char, position: Position },
+    UnterminatedString { position: Position },
+}
 
 // ASCII Only
-struct Lexer {
-    input: Vec<u8>,
+struct Lexer<'src> {
+    input: &'src str,
     position: usize,      // current position in input (points to current char)
     read_position: usize, // current reading position in input (after current char)
     ch: u8,               // current char under examination
+    line: usize,          // line of ch, 1-indexed
+    column: usize,        // column of ch, 1-indexed
+    eof_sent: bool,       // true once the Iterator impl has stopped at EOF
 }
 
 fn is_letter(c: char) -> bool {
@@ -44,100 +84,259 @@ fn is_number(c: char) -> bool {
     c.is_digit(10)
 }
 
-fn is_keyword(s: &str) -> Option<Token> {
+fn is_keyword(s: &str) -> Option<TokenType> {
     match s {
-        "fn" => Some(Token(TokenType::Function, "fn".chars().collect())),
-        "let" => Some(Token(TokenType::Let, "let".chars().collect())),
+        "fn" => Some(TokenType::Function),
+        "let" => Some(TokenType::Let),
+        "true" => Some(TokenType::True),
+        "false" => Some(TokenType::False),
+        "if" => Some(TokenType::If),
+        "else" => Some(TokenType::Else),
+        "return" => Some(TokenType::Return),
         _ => None,
     }
 }
 
-impl Lexer {
-    fn new(input: &str) -> Self {
+impl<'src> Lexer<'src> {
+    fn new(input: &'src str) -> Self {
         let mut lexer = Lexer {
-            input: input.as_bytes().to_vec(),
+            input,
             position: 0,
             read_position: 0,
             ch: b'\0',
+            line: 1,
+            column: 0,
+            eof_sent: false,
         };
         lexer.read_char(); // Initialize Lexer
         lexer
     }
 
     fn read_char(&mut self) {
+        if self.ch == b'\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+
         if self.read_position >= self.input.len() {
             self.ch = b'\0';
         } else {
-            self.ch = self.input[self.read_position];
+            self.ch = self.input.as_bytes()[self.read_position];
         }
         self.position = self.read_position;
         self.read_position += 1;
     }
 
-    fn next_token(&mut self) -> Token {
+    fn peek_char(&self) -> u8 {
+        if self.read_position >= self.input.len() {
+            b'\0'
+        } else {
+            self.input.as_bytes()[self.read_position]
+        }
+    }
+
+    fn current_position(&self) -> Position {
+        Position {
+            line: self.line,
+            column: self.column,
+        }
+    }
+
+    fn next_token(&mut self) -> Result<SpannedToken<'src>, LexError> {
         self.consume_whitespace();
 
-        let token: Token = match self.ch {
-            b'=' => Token(TokenType::Assign, vec![self.ch as char]),
-            b';' => Token(TokenType::Semicolon, vec![self.ch as char]),
-            b'(' => Token(TokenType::LParen, vec![self.ch as char]),
-            b')' => Token(TokenType::RParen, vec![self.ch as char]),
-            b',' => Token(TokenType::Comma, vec![self.ch as char]),
-            b'+' => Token(TokenType::Plus, vec![self.ch as char]),
-            b'{' => Token(TokenType::LBrace, vec![self.ch as char]),
-            b'}' => Token(TokenType::RBrace, vec![self.ch as char]),
-            b'\0' => Token(TokenType::EOF, vec![self.ch as char]),
+        let start = self.current_position();
+        let token = self.scan_token()?;
+        let end = self.current_position();
+
+        Ok(SpannedToken { token, start, end })
+    }
+
+    fn scan_token(&mut self) -> Result<Token<'src>, LexError> {
+        let token: Token<'src> = match self.ch {
+            b'=' => {
+                if self.peek_char() == b'=' {
+                    let position = self.position;
+                    self.read_char();
+                    self.read_char();
+                    return Ok(Token(TokenType::Eq, Cow::Borrowed(&self.input[position..self.position])));
+                }
+                Token(TokenType::Assign, Cow::Borrowed(&self.input[self.position..self.read_position]))
+            }
+            b'!' => {
+                if self.peek_char() == b'=' {
+                    let position = self.position;
+                    self.read_char();
+                    self.read_char();
+                    return Ok(Token(TokenType::NotEq, Cow::Borrowed(&self.input[position..self.position])));
+                }
+                Token(TokenType::Bang, Cow::Borrowed(&self.input[self.position..self.read_position]))
+            }
+            b'"' => return self.read_string(),
+            b';' => Token(TokenType::Semicolon, Cow::Borrowed(&self.input[self.position..self.read_position])),
+            b'(' => Token(TokenType::LParen, Cow::Borrowed(&self.input[self.position..self.read_position])),
+            b')' => Token(TokenType::RParen, Cow::Borrowed(&self.input[self.position..self.read_position])),
+            b',' => Token(TokenType::Comma, Cow::Borrowed(&self.input[self.position..self.read_position])),
+            b'+' => Token(TokenType::Plus, Cow::Borrowed(&self.input[self.position..self.read_position])),
+            b'-' => Token(TokenType::Minus, Cow::Borrowed(&self.input[self.position..self.read_position])),
+            b'*' => Token(TokenType::Asterisk, Cow::Borrowed(&self.input[self.position..self.read_position])),
+            b'/' => Token(TokenType::Slash, Cow::Borrowed(&self.input[self.position..self.read_position])),
+            b'<' => Token(TokenType::Lt, Cow::Borrowed(&self.input[self.position..self.read_position])),
+            b'>' => Token(TokenType::Gt, Cow::Borrowed(&self.input[self.position..self.read_position])),
+            b'{' => Token(TokenType::LBrace, Cow::Borrowed(&self.input[self.position..self.read_position])),
+            b'}' => Token(TokenType::RBrace, Cow::Borrowed(&self.input[self.position..self.read_position])),
+            b'\0' => Token(TokenType::EOF, Cow::Borrowed("")),
             ch => {
                 if is_letter(ch as char) {
                     let value = self.read_identifier();
-                    if let Some(token) = is_keyword(&value) {
-                        return token;
-                    } else {
-                        return Token(TokenType::Ident, value.chars().collect());
-                    }
+                    return Ok(match is_keyword(value) {
+                        Some(keyword) => Token(keyword, Cow::Borrowed(value)),
+                        None => Token(TokenType::Ident, Cow::Borrowed(value)),
+                    });
                 } else if is_number(ch as char) {
-                    return Token(TokenType::Int, self.read_number());
+                    return Ok(Token(TokenType::Int, Cow::Borrowed(self.read_number())));
                 } else {
-                    Token(TokenType::Illegal, vec![ch as char])
+                    let position = self.current_position();
+                    return Err(LexError::UnexpectedChar { ch: ch as char, position });
                 }
             }
         };
         self.read_char();
-        token
+        Ok(token)
     }
 
-    fn read_identifier(&mut self) -> String {
+    /// Reads a `"..."` string literal, decoding `\n`, `\t`, `\r`, `\"` and
+    /// `\\` escapes. Falls back to an owned buffer only when an escape is
+    /// actually present; plain strings stay borrowed from `input`.
+    ///
+    /// Returns `LexError::UnterminatedString` if EOF is reached before the
+    /// closing quote.
+    fn read_string(&mut self) -> Result<Token<'src>, LexError> {
+        let start_position = self.current_position();
+        self.read_char(); // consume opening quote
+
+        let mut buffer: Option<String> = None;
+        let mut segment_start = self.position;
+        loop {
+            match self.ch {
+                b'\0' => {
+                    return Err(LexError::UnterminatedString { position: start_position });
+                }
+                b'"' => {
+                    let value = match buffer {
+                        Some(mut buffer) => {
+                            buffer.push_str(&self.input[segment_start..self.position]);
+                            Cow::Owned(buffer)
+                        }
+                        None => Cow::Borrowed(&self.input[segment_start..self.position]),
+                    };
+                    self.read_char(); // consume closing quote
+                    return Ok(Token(TokenType::String, value));
+                }
+                b'\\' => {
+                    let buffer = buffer.get_or_insert_with(String::new);
+                    buffer.push_str(&self.input[segment_start..self.position]);
+                    self.read_char();
+                    let decoded = match self.ch {
+                        b'n' => b'\n',
+                        b't' => b'\t',
+                        b'r' => b'\r',
+                        b'"' => b'"',
+                        b'\\' => b'\\',
+                        other => other,
+                    };
+                    buffer.push(decoded as char);
+                    self.read_char();
+                    segment_start = self.position;
+                }
+                _ => self.read_char(),
+            }
+        }
+    }
+
+    fn read_identifier(&mut self) -> &'src str {
         let position = self.position;
         while is_letter(self.ch as char) {
             self.read_char();
         }
-        let identifier_bytes = &self.input[position..self.position];
-        identifier_bytes.iter().map(|byte| *byte as char).collect()
+        &self.input[position..self.position]
     }
 
-    fn read_number(&mut self) -> Vec<char> {
+    fn read_number(&mut self) -> &'src str {
         let position = self.position;
         while is_number(self.ch as char) {
             self.read_char();
         }
-        let number_bytes = &self.input[position..self.position];
-        number_bytes.iter().map(|byte| *byte as char).collect()
+        &self.input[position..self.position]
     }
 
     fn consume_whitespace(&mut self) {
-        while self.ch == b' ' || self.ch == b'\t' || self.ch == b'\n' || self.ch == b'\r' {
-            self.read_char()
+        loop {
+            while self.ch == b' ' || self.ch == b'\t' || self.ch == b'\n' || self.ch == b'\r' {
+                self.read_char()
+            }
+            if self.ch == b'/' && (self.peek_char() == b'/' || self.peek_char() == b'*') {
+                self.skip_comment();
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Skips a `//` line comment or a `/* */` block comment, both consumed
+    /// as whitespace before the next token is scanned. Block comments may
+    /// nest; an unterminated block comment is consumed up to EOF.
+    fn skip_comment(&mut self) {
+        self.read_char(); // consume the leading '/'
+        if self.ch == b'/' {
+            while self.ch != b'\n' && self.ch != b'\0' {
+                self.read_char();
+            }
+        } else if self.ch == b'*' {
+            self.read_char(); // consume the '*'
+            let mut depth = 1;
+            while depth > 0 && self.ch != b'\0' {
+                if self.ch == b'*' && self.peek_char() == b'/' {
+                    self.read_char();
+                    self.read_char();
+                    depth -= 1;
+                } else if self.ch == b'/' && self.peek_char() == b'*' {
+                    self.read_char();
+                    self.read_char();
+                    depth += 1;
+                } else {
+                    self.read_char();
+                }
+            }
         }
     }
 
-    fn lex(mut self) -> Vec<Token> {
-        let mut token = self.next_token();
-        let mut tokens: Vec<Token> = Vec::new();
-        while token.0 != TokenType::EOF {
-            tokens.push(token);
-            token = self.next_token();
+    fn lex(self) -> Result<Vec<SpannedToken<'src>>, LexError> {
+        self.collect()
+    }
+}
+
+impl<'src> Iterator for Lexer<'src> {
+    type Item = Result<SpannedToken<'src>, LexError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.eof_sent {
+            return None;
+        }
+
+        match self.next_token() {
+            Ok(token) if token.token.0 == TokenType::EOF => {
+                self.eof_sent = true;
+                None
+            }
+            Ok(token) => Some(Ok(token)),
+            Err(err) => {
+                self.eof_sent = true;
+                Some(Err(err))
+            }
         }
-        tokens
     }
 }
 
@@ -145,7 +344,8 @@ impl Lexer {
 mod tests {
     use super::*;
 
-    fn compare_tokens(left: Vec<Token>, right: Vec<Token>) {
+    fn compare_tokens(left: Vec<Token>, right: Vec<SpannedToken>) {
+        let right: Vec<Token> = right.into_iter().map(|spanned| spanned.token).collect();
         let token_iter = left.iter().zip(right.iter()).enumerate();
         for (index, (left_token, right_token)) in token_iter {
             if left_token != right_token {
@@ -160,17 +360,17 @@ mod tests {
     fn basic_tokens() {
         let input = "=+(){},;";
         let lexer = Lexer::new(input);
-        let tokens = lexer.lex();
+        let tokens = lexer.lex().unwrap();
         compare_tokens(
             vec![
-                Token(TokenType::Assign, vec!['=']),
-                Token(TokenType::Plus, vec!['+']),
-                Token(TokenType::LParen, vec!['(']),
-                Token(TokenType::RParen, vec![')']),
-                Token(TokenType::LBrace, vec!['{']),
-                Token(TokenType::RBrace, vec!['}']),
-                Token(TokenType::Comma, vec![',']),
-                Token(TokenType::Semicolon, vec![';']),
+                Token(TokenType::Assign, "=".into()),
+                Token(TokenType::Plus, "+".into()),
+                Token(TokenType::LParen, "(".into()),
+                Token(TokenType::RParen, ")".into()),
+                Token(TokenType::LBrace, "{".into()),
+                Token(TokenType::RBrace, "}".into()),
+                Token(TokenType::Comma, ",".into()),
+                Token(TokenType::Semicolon, ";".into()),
             ],
             tokens,
         );
@@ -187,46 +387,213 @@ mod tests {
         let result = add(five, ten)
     ";
         let lexer = Lexer::new(input);
-        let tokens = lexer.lex();
+        let tokens = lexer.lex().unwrap();
         compare_tokens(
             vec![
-                Token(TokenType::Let, "let".chars().collect()),
-                Token(TokenType::Ident, "five".chars().collect()),
-                Token(TokenType::Assign, vec!['=']),
-                Token(TokenType::Int, "5".chars().collect()),
-                Token(TokenType::Semicolon, vec![';']),
-                Token(TokenType::Let, "let".chars().collect()),
-                Token(TokenType::Ident, "ten".chars().collect()),
-                Token(TokenType::Assign, vec!['=']),
-                Token(TokenType::Int, "10".chars().collect()),
-                Token(TokenType::Semicolon, vec![';']),
-                Token(TokenType::Let, "let".chars().collect()),
-                Token(TokenType::Ident, "add".chars().collect()),
-                Token(TokenType::Assign, vec!['=']),
-                Token(TokenType::Function, "fn".chars().collect()),
-                Token(TokenType::LParen, vec!['(']),
-                Token(TokenType::Ident, "x".chars().collect()),
-                Token(TokenType::Comma, vec![',']),
-                Token(TokenType::Ident, "y".chars().collect()),
-                Token(TokenType::RParen, vec![')']),
-                Token(TokenType::LBrace, vec!['{']),
-                Token(TokenType::Ident, "x".chars().collect()),
-                Token(TokenType::Plus, vec!['+']),
-                Token(TokenType::Ident, "y".chars().collect()),
-                Token(TokenType::Semicolon, vec![';']),
-                Token(TokenType::RBrace, vec!['}']),
-                Token(TokenType::Semicolon, vec![';']),
-                Token(TokenType::Let, "let".chars().collect()),
-                Token(TokenType::Ident, "result".chars().collect()),
-                Token(TokenType::Assign, vec!['=']),
-                Token(TokenType::Ident, "add".chars().collect()),
-                Token(TokenType::LParen, vec!['(']),
-                Token(TokenType::Ident, "five".chars().collect()),
-                Token(TokenType::Comma, vec![',']),
-                Token(TokenType::Ident, "ten".chars().collect()),
-                Token(TokenType::RParen, vec![')']),
+                Token(TokenType::Let, "let".into()),
+                Token(TokenType::Ident, "five".into()),
+                Token(TokenType::Assign, "=".into()),
+                Token(TokenType::Int, "5".into()),
+                Token(TokenType::Semicolon, ";".into()),
+                Token(TokenType::Let, "let".into()),
+                Token(TokenType::Ident, "ten".into()),
+                Token(TokenType::Assign, "=".into()),
+                Token(TokenType::Int, "10".into()),
+                Token(TokenType::Semicolon, ";".into()),
+                Token(TokenType::Let, "let".into()),
+                Token(TokenType::Ident, "add".into()),
+                Token(TokenType::Assign, "=".into()),
+                Token(TokenType::Function, "fn".into()),
+                Token(TokenType::LParen, "(".into()),
+                Token(TokenType::Ident, "x".into()),
+                Token(TokenType::Comma, ",".into()),
+                Token(TokenType::Ident, "y".into()),
+                Token(TokenType::RParen, ")".into()),
+                Token(TokenType::LBrace, "{".into()),
+                Token(TokenType::Ident, "x".into()),
+                Token(TokenType::Plus, "+".into()),
+                Token(TokenType::Ident, "y".into()),
+                Token(TokenType::Semicolon, ";".into()),
+                Token(TokenType::RBrace, "}".into()),
+                Token(TokenType::Semicolon, ";".into()),
+                Token(TokenType::Let, "let".into()),
+                Token(TokenType::Ident, "result".into()),
+                Token(TokenType::Assign, "=".into()),
+                Token(TokenType::Ident, "add".into()),
+                Token(TokenType::LParen, "(".into()),
+                Token(TokenType::Ident, "five".into()),
+                Token(TokenType::Comma, ",".into()),
+                Token(TokenType::Ident, "ten".into()),
+                Token(TokenType::RParen, ")".into()),
             ],
             tokens,
         );
     }
+
+    #[test]
+    fn string_literals() {
+        let input = r#""foobar" "foo bar" "escaped\n\t\"\\""#;
+        let lexer = Lexer::new(input);
+        let tokens = lexer.lex().unwrap();
+        compare_tokens(
+            vec![
+                Token(TokenType::String, "foobar".into()),
+                Token(TokenType::String, "foo bar".into()),
+                Token(TokenType::String, "escaped\n\t\"\\".into()),
+            ],
+            tokens,
+        );
+    }
+
+    #[test]
+    fn unterminated_string_literal_is_a_lex_error() {
+        let input = "\"foo";
+        let lexer = Lexer::new(input);
+        let err = lexer.lex().unwrap_err();
+        assert_eq!(
+            err,
+            LexError::UnterminatedString {
+                position: Position { line: 1, column: 1 }
+            }
+        );
+    }
+
+    #[test]
+    fn unexpected_char_is_a_lex_error() {
+        let input = "let x = @;";
+        let lexer = Lexer::new(input);
+        let err = lexer.lex().unwrap_err();
+        assert_eq!(
+            err,
+            LexError::UnexpectedChar {
+                ch: '@',
+                position: Position { line: 1, column: 9 }
+            }
+        );
+    }
+
+    #[test]
+    fn skips_line_and_block_comments() {
+        let input = "
+        // a line comment
+        let five = 5; // trailing comment
+        /* a block
+           comment */
+        let /* nested /* block */ comment */ ten = 10;
+    ";
+        let lexer = Lexer::new(input);
+        let tokens = lexer.lex().unwrap();
+        compare_tokens(
+            vec![
+                Token(TokenType::Let, "let".into()),
+                Token(TokenType::Ident, "five".into()),
+                Token(TokenType::Assign, "=".into()),
+                Token(TokenType::Int, "5".into()),
+                Token(TokenType::Semicolon, ";".into()),
+                Token(TokenType::Let, "let".into()),
+                Token(TokenType::Ident, "ten".into()),
+                Token(TokenType::Assign, "=".into()),
+                Token(TokenType::Int, "10".into()),
+                Token(TokenType::Semicolon, ";".into()),
+            ],
+            tokens,
+        );
+    }
+
+    #[test]
+    fn lexer_is_a_token_iterator() {
+        let input = "+;";
+        let lexer = Lexer::new(input);
+        let tokens: Vec<Token> = lexer
+            .map(|spanned| spanned.unwrap().token)
+            .collect();
+        assert_eq!(
+            tokens,
+            vec![
+                Token(TokenType::Plus, "+".into()),
+                Token(TokenType::Semicolon, ";".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn operators_and_keywords() {
+        let input = "
+        !- / * 5;
+        5 < 10 > 5;
+
+        if (5 < 10) {
+            return true;
+        } else {
+            return false;
+        }
+
+        10 == 10;
+        10 != 9;
+    ";
+        let lexer = Lexer::new(input);
+        let tokens = lexer.lex().unwrap();
+        compare_tokens(
+            vec![
+                Token(TokenType::Bang, "!".into()),
+                Token(TokenType::Minus, "-".into()),
+                Token(TokenType::Slash, "/".into()),
+                Token(TokenType::Asterisk, "*".into()),
+                Token(TokenType::Int, "5".into()),
+                Token(TokenType::Semicolon, ";".into()),
+                Token(TokenType::Int, "5".into()),
+                Token(TokenType::Lt, "<".into()),
+                Token(TokenType::Int, "10".into()),
+                Token(TokenType::Gt, ">".into()),
+                Token(TokenType::Int, "5".into()),
+                Token(TokenType::Semicolon, ";".into()),
+                Token(TokenType::If, "if".into()),
+                Token(TokenType::LParen, "(".into()),
+                Token(TokenType::Int, "5".into()),
+                Token(TokenType::Lt, "<".into()),
+                Token(TokenType::Int, "10".into()),
+                Token(TokenType::RParen, ")".into()),
+                Token(TokenType::LBrace, "{".into()),
+                Token(TokenType::Return, "return".into()),
+                Token(TokenType::True, "true".into()),
+                Token(TokenType::Semicolon, ";".into()),
+                Token(TokenType::RBrace, "}".into()),
+                Token(TokenType::Else, "else".into()),
+                Token(TokenType::LBrace, "{".into()),
+                Token(TokenType::Return, "return".into()),
+                Token(TokenType::False, "false".into()),
+                Token(TokenType::Semicolon, ";".into()),
+                Token(TokenType::RBrace, "}".into()),
+                Token(TokenType::Int, "10".into()),
+                Token(TokenType::Eq, "==".into()),
+                Token(TokenType::Int, "10".into()),
+                Token(TokenType::Semicolon, ";".into()),
+                Token(TokenType::Int, "10".into()),
+                Token(TokenType::NotEq, "!=".into()),
+                Token(TokenType::Int, "9".into()),
+                Token(TokenType::Semicolon, ";".into()),
+            ],
+            tokens,
+        );
+    }
+
+    #[test]
+    fn tracks_line_and_column_spans() {
+        let input = "let x =\n  5;";
+        let lexer = Lexer::new(input);
+        let tokens = lexer.lex().unwrap();
+
+        let spans: Vec<(Position, Position)> =
+            tokens.iter().map(|t| (t.start, t.end)).collect();
+        assert_eq!(
+            spans,
+            vec![
+                (Position { line: 1, column: 1 }, Position { line: 1, column: 4 }),
+                (Position { line: 1, column: 5 }, Position { line: 1, column: 6 }),
+                (Position { line: 1, column: 7 }, Position { line: 1, column: 8 }),
+                (Position { line: 2, column: 3 }, Position { line: 2, column: 4 }),
+                (Position { line: 2, column: 4 }, Position { line: 2, column: 5 }),
+            ]
+        );
+    }
 }